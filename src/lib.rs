@@ -1,15 +1,21 @@
 pub mod image {
-    use std::{error::Error, fs::File, usize};
+    use std::{error::Error, fs::File, io::Read, usize};
 
-    use png::{BitDepth, ColorType, DecodingError};
+    use png::{BitDepth, ColorType, DecodingError, EncodingError};
 
+    #[derive(Debug)]
     pub struct ImageData {
         width: usize,
         height: usize,
         color: png::ColorType,
         bit_depth: png::BitDepth,
 
-        pixels: Vec<u8>
+        pixels: Vec<u8>,
+
+        // PLTE / tRNS chunks as read from the file, if present; only used for
+        // `ColorType::Indexed` images.
+        palette: Option<Vec<u8>>,
+        trns: Option<Vec<u8>>,
     }
 
     #[derive(Debug, Copy, Clone)]
@@ -20,6 +26,11 @@ pub mod image {
         g: usize,
         b: usize,
         a: usize,
+
+        // The raw PLTE index this pixel was resolved from, for `ColorType::Indexed`
+        // images. `r/g/b/a` above are the looked-up color, which PNG's indexed
+        // format can't store directly, so `set_pixel_at` writes this back instead.
+        index: Option<usize>,
     }
 
     impl PixelData {
@@ -29,7 +40,19 @@ pub mod image {
                 r,
                 g,
                 b,
-                a
+                a,
+                index: None,
+            }
+        }
+
+        pub fn with_index(bit_depth: png::BitDepth, r: usize, g: usize, b: usize, a: usize, index: usize) -> Self {
+            PixelData {
+                bit_depth,
+                r,
+                g,
+                b,
+                a,
+                index: Some(index),
             }
         }
 
@@ -49,6 +72,10 @@ pub mod image {
             self.a
         }
 
+        pub fn index(&self) -> Option<usize> {
+            self.index
+        }
+
         pub fn bit_depth(&self) -> &png::BitDepth {
             &self.bit_depth
         }
@@ -77,7 +104,21 @@ pub mod image {
                 height,
                 color,
                 bit_depth,
-                pixels
+                pixels,
+                palette: None,
+                trns: None,
+            }
+        }
+
+        pub fn with_palette(width: usize, height: usize, color: png::ColorType, bit_depth: png::BitDepth, pixels: Vec<u8>, palette: Option<Vec<u8>>, trns: Option<Vec<u8>>) -> Self {
+            ImageData {
+                width,
+                height,
+                color,
+                bit_depth,
+                pixels,
+                palette,
+                trns,
             }
         }
 
@@ -89,7 +130,19 @@ pub mod image {
             self.height
         }
 
-        pub fn get_pixel_at(&self, x: usize, y: usize) -> PixelData {
+        pub fn palette(&self) -> Option<&Vec<u8>> {
+            self.palette.as_ref()
+        }
+
+        pub fn trns(&self) -> Option<&Vec<u8>> {
+            self.trns.as_ref()
+        }
+
+        pub fn get_pixel_at(&self, x: usize, y: usize) -> Option<PixelData> {
+            if x >= self.width || y >= self.height {
+                return None;
+            }
+
             let bytes = f64::from(self.bit_depth as u32) / 8.0;
             let elements = match self.color {
                 ColorType::Rgba => 4,
@@ -101,12 +154,24 @@ pub mod image {
 
             let index: usize = (f64::from((y * self.width + x) as u32 * elements) * bytes) as usize;
 
-            if index > self.pixels.len() {
-                return PixelData::new(BitDepth::Eight, 0, 0, 0, 0);
+            // Number of bytes read starting at `index` by the match below, so we can
+            // reject an out-of-bounds read instead of silently returning zeroes.
+            let bytes_needed = match self.bit_depth {
+                BitDepth::Sixteen => elements as usize * 2,
+                BitDepth::Eight => elements as usize,
+                BitDepth::Four => match self.color {
+                    ColorType::Rgba | ColorType::Rgb => 2,
+                    ColorType::Grayscale | ColorType::GrayscaleAlpha | ColorType::Indexed => 1,
+                },
+                BitDepth::One | BitDepth::Two => 0,
+            };
+
+            if self.bit_depth != BitDepth::One && self.bit_depth != BitDepth::Two && index + bytes_needed > self.pixels.len() {
+                return None;
             }
 
             let pixel: PixelData;
-            
+
             match self.bit_depth {
                 BitDepth::Eight => {
                     match self.color {
@@ -123,7 +188,7 @@ pub mod image {
                             pixel = PixelData::new(self.bit_depth, self.pixels[index].into(), 0, 0, self.pixels[index + 1].into())
                         }
                         ColorType::Indexed => {
-                            pixel = PixelData::new(self.bit_depth, self.pixels[index].into(), 0, 0, 0)
+                            pixel = self.palette_lookup(self.pixels[index] as usize)
                         }
                     }
                 }
@@ -136,58 +201,581 @@ pub mod image {
                             pixel = PixelData::new(self.bit_depth, (self.pixels[index] >> 4).into(), (self.pixels[index] << 4 >> 4).into(), (self.pixels[index + 1] >> 4).into(), 0)
                         }
                         ColorType::Grayscale => {
-                            pixel = PixelData::new(self.bit_depth, (self.pixels[index] >> 4).into(), 0, 0, 0)
+                            // 4-bit grayscale packs two samples per byte, MSB-first, so
+                            // (unlike the Rgba/Rgb arms above and the GrayscaleAlpha arm below,
+                            // which read a whole pixel's channels starting at `index`) we must pick the nibble that
+                            // actually belongs to `x` instead of always reading the high one.
+                            let row_bytes = (self.width * 4).div_ceil(8);
+                            let byte_index = y * row_bytes + x / 2;
+
+                            if byte_index >= self.pixels.len() {
+                                return None;
+                            }
+
+                            let byte = self.pixels[byte_index];
+                            let sample = if x.is_multiple_of(2) { byte >> 4 } else { byte & 0x0F };
+
+                            pixel = PixelData::new(self.bit_depth, sample as usize, 0, 0, 0)
                         }
                         ColorType::GrayscaleAlpha => {
                             pixel = PixelData::new(self.bit_depth, (self.pixels[index] >> 4).into(), 0, 0, (self.pixels[index] << 4 >> 4).into())
                         }
                         ColorType::Indexed => {
-                            pixel = PixelData::new(self.bit_depth, (self.pixels[index] >> 4).into(), 0, 0, 0)
+                            // 4-bit indexed packs two palette indices per byte, MSB-first, so
+                            // (like the Grayscale arm above) we must pick the nibble that
+                            // actually belongs to `x` instead of always reading the high one.
+                            let row_bytes = (self.width * 4).div_ceil(8);
+                            let byte_index = y * row_bytes + x / 2;
+
+                            if byte_index >= self.pixels.len() {
+                                return None;
+                            }
+
+                            let byte = self.pixels[byte_index];
+                            let idx = if x.is_multiple_of(2) { byte >> 4 } else { byte & 0x0F };
+
+                            pixel = self.palette_lookup(idx as usize)
+                        }
+                    }
+                }
+                BitDepth::Sixteen => {
+                    let read16 = |i: usize| -> usize { (self.pixels[i] as usize) << 8 | self.pixels[i + 1] as usize };
+
+                    match self.color {
+                        ColorType::Rgba => {
+                            pixel = PixelData::new(self.bit_depth, read16(index), read16(index + 2), read16(index + 4), read16(index + 6))
+                        }
+                        ColorType::Rgb => {
+                            pixel = PixelData::new(self.bit_depth, read16(index), read16(index + 2), read16(index + 4), 1)
+                        }
+                        ColorType::Grayscale => {
+                            pixel = PixelData::new(self.bit_depth, read16(index), 0, 0, 0)
+                        }
+                        ColorType::GrayscaleAlpha => {
+                            pixel = PixelData::new(self.bit_depth, read16(index), 0, 0, read16(index + 2))
+                        }
+                        ColorType::Indexed => {
+                            pixel = self.palette_lookup(read16(index))
+                        }
+                    }
+                }
+                BitDepth::One | BitDepth::Two => {
+                    let bits = self.bit_depth as usize;
+                    let row_bytes = (self.width * bits).div_ceil(8);
+                    let bit_offset = x * bits;
+                    let byte_index = y * row_bytes + bit_offset / 8;
+
+                    if byte_index >= self.pixels.len() {
+                        return None;
+                    }
+
+                    let byte = self.pixels[byte_index];
+                    let sample = ((byte >> (8 - bits - (bit_offset % 8))) & ((1 << bits) - 1)) as usize;
+
+                    match self.color {
+                        ColorType::Grayscale => {
+                            pixel = PixelData::new(self.bit_depth, sample, 0, 0, 0)
+                        }
+                        ColorType::Indexed => {
+                            pixel = self.palette_lookup(sample)
+                        }
+                        _ => {
+                            pixel = PixelData::new(self.bit_depth, sample, 0, 0, 0)
                         }
                     }
                 }
-                // TODO: Implement support for all bit depths
-                _ => {
-                    pixel = PixelData::new(BitDepth::Eight, 0, 0, 0, 0);
+            };
+
+            Some(pixel)
+        }
+
+        // Resolves a PLTE palette index (plus the matching tRNS entry, if any) into an RGBA pixel.
+        fn palette_lookup(&self, index: usize) -> PixelData {
+            let entry = index * 3;
+            let (r, g, b) = match &self.palette {
+                Some(palette) if entry + 2 < palette.len() => {
+                    (palette[entry] as usize, palette[entry + 1] as usize, palette[entry + 2] as usize)
                 }
+                _ => (0, 0, 0),
+            };
+
+            let a = match &self.trns {
+                Some(trns) if index < trns.len() => trns[index] as usize,
+                _ => 255,
             };
 
-            pixel
+            PixelData::with_index(self.bit_depth, r, g, b, a, index)
+        }
+
+        // Writes `pixel` back into the packed buffer at (x, y), using the same
+        // index/stride logic as `get_pixel_at`. Out-of-range coordinates are a no-op.
+        // For `ColorType::Indexed` images, `pixel.index()` (populated by `palette_lookup`)
+        // is written back verbatim, since indexed PNGs can only store a palette index, not
+        // an arbitrary color; pixels not obtained from `get_pixel_at` fall back to treating
+        // `pixel.r()` as the raw index.
+        pub fn set_pixel_at(&mut self, x: usize, y: usize, pixel: &PixelData) {
+            if x >= self.width || y >= self.height {
+                return;
+            }
+
+            let bytes = f64::from(self.bit_depth as u32) / 8.0;
+            let elements = match self.color {
+                ColorType::Rgba => 4,
+                ColorType::Rgb => 3,
+                ColorType::GrayscaleAlpha => 2,
+                ColorType::Grayscale => 1,
+                ColorType::Indexed => 1
+            } as u32;
+
+            let index: usize = (f64::from((y * self.width + x) as u32 * elements) * bytes) as usize;
+
+            match self.bit_depth {
+                BitDepth::Eight => {
+                    match self.color {
+                        ColorType::Rgba => {
+                            if index + 3 >= self.pixels.len() { return; }
+                            self.pixels[index] = pixel.r() as u8;
+                            self.pixels[index + 1] = pixel.g() as u8;
+                            self.pixels[index + 2] = pixel.b() as u8;
+                            self.pixels[index + 3] = pixel.a() as u8;
+                        }
+                        ColorType::Rgb => {
+                            if index + 2 >= self.pixels.len() { return; }
+                            self.pixels[index] = pixel.r() as u8;
+                            self.pixels[index + 1] = pixel.g() as u8;
+                            self.pixels[index + 2] = pixel.b() as u8;
+                        }
+                        ColorType::Grayscale => {
+                            if index >= self.pixels.len() { return; }
+                            self.pixels[index] = pixel.r() as u8;
+                        }
+                        ColorType::GrayscaleAlpha => {
+                            if index + 1 >= self.pixels.len() { return; }
+                            self.pixels[index] = pixel.r() as u8;
+                            self.pixels[index + 1] = pixel.a() as u8;
+                        }
+                        ColorType::Indexed => {
+                            if index >= self.pixels.len() { return; }
+                            self.pixels[index] = pixel.index().unwrap_or_else(|| pixel.r()) as u8;
+                        }
+                    }
+                }
+                BitDepth::Four => {
+                    match self.color {
+                        ColorType::Rgba => {
+                            if index + 1 >= self.pixels.len() { return; }
+                            self.pixels[index] = ((pixel.r() as u8) << 4) | (pixel.g() as u8 & 0x0F);
+                            self.pixels[index + 1] = ((pixel.b() as u8) << 4) | (pixel.a() as u8 & 0x0F);
+                        }
+                        ColorType::Rgb => {
+                            if index + 1 >= self.pixels.len() { return; }
+                            self.pixels[index] = ((pixel.r() as u8) << 4) | (pixel.g() as u8 & 0x0F);
+                            self.pixels[index + 1] = (self.pixels[index + 1] & 0x0F) | ((pixel.b() as u8) << 4);
+                        }
+                        ColorType::Grayscale => {
+                            // Same nibble-packing as the Indexed arm below: pick the nibble
+                            // belonging to `x` and preserve its neighbour rather than always
+                            // writing the high one.
+                            let row_bytes = (self.width * 4).div_ceil(8);
+                            let byte_index = y * row_bytes + x / 2;
+
+                            if byte_index >= self.pixels.len() { return; }
+
+                            let sample = (pixel.r() as u8) & 0x0F;
+                            self.pixels[byte_index] = if x.is_multiple_of(2) {
+                                (sample << 4) | (self.pixels[byte_index] & 0x0F)
+                            } else {
+                                (self.pixels[byte_index] & 0xF0) | sample
+                            };
+                        }
+                        ColorType::GrayscaleAlpha => {
+                            if index >= self.pixels.len() { return; }
+                            self.pixels[index] = ((pixel.r() as u8) << 4) | (pixel.a() as u8 & 0x0F);
+                        }
+                        ColorType::Indexed => {
+                            // Same nibble-packing as the read side: pick the nibble belonging to
+                            // `x` and preserve its neighbour rather than always writing the high one.
+                            let row_bytes = (self.width * 4).div_ceil(8);
+                            let byte_index = y * row_bytes + x / 2;
+
+                            if byte_index >= self.pixels.len() { return; }
+
+                            let idx = (pixel.index().unwrap_or_else(|| pixel.r()) as u8) & 0x0F;
+                            self.pixels[byte_index] = if x.is_multiple_of(2) {
+                                (idx << 4) | (self.pixels[byte_index] & 0x0F)
+                            } else {
+                                (self.pixels[byte_index] & 0xF0) | idx
+                            };
+                        }
+                    }
+                }
+                BitDepth::Sixteen => {
+                    let write16 = |pixels: &mut Vec<u8>, i: usize, v: usize| {
+                        pixels[i] = (v >> 8) as u8;
+                        pixels[i + 1] = v as u8;
+                    };
+
+                    match self.color {
+                        ColorType::Rgba => {
+                            if index + 7 >= self.pixels.len() { return; }
+                            write16(&mut self.pixels, index, pixel.r());
+                            write16(&mut self.pixels, index + 2, pixel.g());
+                            write16(&mut self.pixels, index + 4, pixel.b());
+                            write16(&mut self.pixels, index + 6, pixel.a());
+                        }
+                        ColorType::Rgb => {
+                            if index + 5 >= self.pixels.len() { return; }
+                            write16(&mut self.pixels, index, pixel.r());
+                            write16(&mut self.pixels, index + 2, pixel.g());
+                            write16(&mut self.pixels, index + 4, pixel.b());
+                        }
+                        ColorType::Grayscale => {
+                            if index + 1 >= self.pixels.len() { return; }
+                            write16(&mut self.pixels, index, pixel.r());
+                        }
+                        ColorType::GrayscaleAlpha => {
+                            if index + 3 >= self.pixels.len() { return; }
+                            write16(&mut self.pixels, index, pixel.r());
+                            write16(&mut self.pixels, index + 2, pixel.a());
+                        }
+                        ColorType::Indexed => {
+                            if index + 1 >= self.pixels.len() { return; }
+                            write16(&mut self.pixels, index, pixel.index().unwrap_or_else(|| pixel.r()));
+                        }
+                    }
+                }
+                BitDepth::One | BitDepth::Two => {
+                    let bits = self.bit_depth as usize;
+                    let row_bytes = (self.width * bits).div_ceil(8);
+                    let bit_offset = x * bits;
+                    let byte_index = y * row_bytes + bit_offset / 8;
+
+                    if byte_index >= self.pixels.len() {
+                        return;
+                    }
+
+                    let shift = 8 - bits - (bit_offset % 8);
+                    let mask: u8 = ((1u8 << bits) - 1) << shift;
+                    let raw = match self.color {
+                        ColorType::Indexed => pixel.index().unwrap_or_else(|| pixel.r()),
+                        _ => pixel.r(),
+                    };
+                    let sample = (raw as u8) & ((1u8 << bits) - 1);
+
+                    self.pixels[byte_index] = (self.pixels[byte_index] & !mask) | (sample << shift);
+                }
+            }
         }
     }
 
-    fn get_decoder(path: &str) -> Result<png::Decoder<File>, std::io::Error> {
-        Ok(png::Decoder::new(File::open(path)?))
+    /// Structured failure modes for the decode path, so callers can match on the
+    /// cause instead of string-matching a `Box<dyn Error>`.
+    #[derive(Debug)]
+    pub enum ImageError {
+        Io(std::io::Error),
+        Decoding(DecodingError),
+        BufferOverflow,
+        BufferExceedsLimit { size: usize, limit: usize },
+        UnsupportedColorBitDepth { color: ColorType, bit_depth: BitDepth },
     }
 
-    fn get_reader(decoder: png::Decoder<File>) -> Result<png::Reader<File>, DecodingError> {
-        decoder.read_info()
+    impl std::fmt::Display for ImageError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                ImageError::Io(e) => write!(f, "I/O error: {}", e),
+                ImageError::Decoding(e) => write!(f, "PNG decoding error: {}", e),
+                ImageError::BufferOverflow => write!(f, "decoded image buffer size overflows usize"),
+                ImageError::BufferExceedsLimit { size, limit } => {
+                    write!(f, "decoded image buffer size {} exceeds limit {}", size, limit)
+                }
+                ImageError::UnsupportedColorBitDepth { color, bit_depth } => {
+                    write!(f, "unsupported combination of color type {:?} and bit depth {:?}", color, bit_depth)
+                }
+            }
+        }
     }
 
-    fn get_image(mut reader: png::Reader<File>) -> Result<ImageData, DecodingError> {
-        let mut buf = vec![0; reader.output_buffer_size()];
+    impl Error for ImageError {}
 
-        let info = reader.next_frame(&mut buf)?;
+    impl From<std::io::Error> for ImageError {
+        fn from(e: std::io::Error) -> Self {
+            ImageError::Io(e)
+        }
+    }
+
+    impl From<DecodingError> for ImageError {
+        fn from(e: DecodingError) -> Self {
+            ImageError::Decoding(e)
+        }
+    }
+
+    fn get_decoder<R: Read>(r: R) -> png::Decoder<R> {
+        png::Decoder::new(r)
+    }
 
-        let bytes = &buf[..info.buffer_size()]; // Get a splice of correct size. Returned frame might be smaller than output buffer.
+    fn get_reader<R: Read>(decoder: png::Decoder<R>) -> Result<png::Reader<R>, ImageError> {
+        Ok(decoder.read_info()?)
+    }
+
+    fn get_image<R: Read>(mut reader: png::Reader<R>, max_size: Option<usize>) -> Result<ImageData, ImageError> {
         let (color_type, bit_depth) = reader.output_color_type();
 
-        Ok(ImageData::new(info.width as usize, info.height as usize, color_type, bit_depth, Vec::from(bytes)))
+        let elements = match color_type {
+            ColorType::Rgba => 4,
+            ColorType::Rgb => 3,
+            ColorType::GrayscaleAlpha => 2,
+            ColorType::Grayscale => 1,
+            ColorType::Indexed => 1,
+        };
+        let bytes_per_sample = match bit_depth {
+            BitDepth::Sixteen => 2,
+            BitDepth::One | BitDepth::Two | BitDepth::Four | BitDepth::Eight => 1,
+        };
+
+        let supported = match color_type {
+            ColorType::Grayscale => true,
+            ColorType::Indexed => bit_depth != BitDepth::Sixteen,
+            ColorType::Rgb | ColorType::GrayscaleAlpha | ColorType::Rgba => {
+                matches!(bit_depth, BitDepth::Eight | BitDepth::Sixteen)
+            }
+        };
+
+        if !supported {
+            return Err(ImageError::UnsupportedColorBitDepth { color: color_type, bit_depth });
+        }
+
+        let info = reader.info();
+        let size = (info.width as usize)
+            .checked_mul(info.height as usize)
+            .and_then(|v| v.checked_mul(elements))
+            .and_then(|v| v.checked_mul(bytes_per_sample))
+            .ok_or(ImageError::BufferOverflow)?;
+
+        if let Some(limit) = max_size {
+            if size > limit {
+                return Err(ImageError::BufferExceedsLimit { size, limit });
+            }
+        }
+
+        let mut buf = vec![0; reader.output_buffer_size()];
+
+        let frame_info = reader.next_frame(&mut buf)?;
+
+        let bytes = &buf[..frame_info.buffer_size()]; // Get a splice of correct size. Returned frame might be smaller than output buffer.
+
+        let palette = reader.info().palette.as_ref().map(|p| p.to_vec());
+        let trns = reader.info().trns.as_ref().map(|t| t.to_vec());
+
+        Ok(ImageData::with_palette(frame_info.width as usize, frame_info.height as usize, color_type, bit_depth, Vec::from(bytes), palette, trns))
     }
 
-    pub fn read_image_from_file(path: &str) -> Result<ImageData, Box<dyn Error>> {
-        let decoder = get_decoder(path)?;
+    pub fn read_image_from_file(path: &str) -> Result<ImageData, ImageError> {
+        let decoder = get_decoder(File::open(path)?);
         let reader = get_reader(decoder)?;
-        Ok(get_image(reader)?)
+        get_image(reader, None)
+    }
+
+    pub fn read_image_from_reader<R: Read>(r: R, max_size: Option<usize>) -> Result<ImageData, ImageError> {
+        let decoder = get_decoder(r);
+        let reader = get_reader(decoder)?;
+        get_image(reader, max_size)
+    }
+
+    pub fn read_image_from_bytes(bytes: &[u8], max_size: Option<usize>) -> Result<ImageData, ImageError> {
+        read_image_from_reader(bytes, max_size)
+    }
+
+    fn get_encoder(path: &str, image: &ImageData) -> Result<png::Encoder<'static, File>, std::io::Error> {
+        let file = File::create(path)?;
+
+        let mut encoder = png::Encoder::new(file, image.width as u32, image.height as u32);
+        encoder.set_color(image.color);
+        encoder.set_depth(image.bit_depth);
+
+        if let ColorType::Indexed = image.color {
+            if let Some(palette) = image.palette() {
+                encoder.set_palette(palette.clone());
+            }
+            if let Some(trns) = image.trns() {
+                encoder.set_trns(trns.clone());
+            }
+        }
+
+        Ok(encoder)
+    }
+
+    fn write_image(encoder: png::Encoder<'static, File>, image: &ImageData) -> Result<(), EncodingError> {
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&image.pixels)?;
+
+        Ok(())
+    }
+
+    pub fn write_image_to_file(image: &ImageData, path: &str) -> Result<(), Box<dyn Error>> {
+        let encoder = get_encoder(path, image)?;
+        Ok(write_image(encoder, image)?)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use image::{ImageData, PixelData};
 
     #[test]
     fn it_works() {
-        
+
+    }
+
+    #[test]
+    fn get_pixel_at_four_bit_indexed_reads_both_nibbles() {
+        let palette = vec![10, 20, 30, 40, 50, 60, 70, 80, 90];
+        let data = ImageData::with_palette(2, 1, png::ColorType::Indexed, png::BitDepth::Four, vec![0x12], Some(palette), None);
+
+        let first = data.get_pixel_at(0, 0).unwrap();
+        assert_eq!((first.r(), first.g(), first.b()), (40, 50, 60));
+
+        let second = data.get_pixel_at(1, 0).unwrap();
+        assert_eq!((second.r(), second.g(), second.b()), (70, 80, 90));
+    }
+
+    #[test]
+    fn set_pixel_at_four_bit_indexed_preserves_neighbor_nibble() {
+        let palette = vec![0, 0, 0, 10, 11, 12, 20, 21, 22, 30, 31, 32, 40, 41, 42, 50, 51, 52];
+        let mut data = ImageData::with_palette(2, 1, png::ColorType::Indexed, png::BitDepth::Four, vec![0x12], Some(palette), None);
+
+        data.set_pixel_at(1, 0, &PixelData::new(png::BitDepth::Four, 5, 0, 0, 0));
+
+        assert_eq!(data.get_pixel_at(0, 0).unwrap().r(), 10);
+        assert_eq!(data.get_pixel_at(1, 0).unwrap().r(), 50);
+    }
+
+    #[test]
+    fn get_pixel_at_four_bit_grayscale_reads_both_nibbles() {
+        let data = ImageData::new(2, 1, png::ColorType::Grayscale, png::BitDepth::Four, vec![0x12]);
+
+        assert_eq!(data.get_pixel_at(0, 0).unwrap().r(), 1);
+        assert_eq!(data.get_pixel_at(1, 0).unwrap().r(), 2);
+    }
+
+    #[test]
+    fn set_pixel_at_four_bit_grayscale_preserves_neighbor_nibble() {
+        let mut data = ImageData::new(2, 1, png::ColorType::Grayscale, png::BitDepth::Four, vec![0x12]);
+
+        data.set_pixel_at(1, 0, &PixelData::new(png::BitDepth::Four, 5, 0, 0, 0));
+
+        assert_eq!(data.get_pixel_at(0, 0).unwrap().r(), 1);
+        assert_eq!(data.get_pixel_at(1, 0).unwrap().r(), 5);
+    }
+
+    #[test]
+    fn get_pixel_at_returns_none_out_of_bounds() {
+        let data = ImageData::new(2, 2, png::ColorType::Grayscale, png::BitDepth::Eight, vec![1, 2, 3, 4]);
+
+        assert!(data.get_pixel_at(2, 0).is_none());
+        assert!(data.get_pixel_at(0, 2).is_none());
+        assert!(data.get_pixel_at(1, 1).is_some());
+    }
+
+    #[test]
+    fn get_pixel_at_one_bit_depth_reads_each_packed_sample() {
+        // A single scanline, 8 one-bit samples packed MSB-first into one byte: 1 0 1 1 0 0 1 0.
+        let data = ImageData::new(8, 1, png::ColorType::Grayscale, png::BitDepth::One, vec![0b1011_0010]);
+
+        let samples: Vec<usize> = (0..8).map(|x| data.get_pixel_at(x, 0).unwrap().r()).collect();
+        assert_eq!(samples, vec![1, 0, 1, 1, 0, 0, 1, 0]);
+    }
+
+    #[test]
+    fn get_pixel_at_two_bit_depth_reads_each_packed_sample() {
+        // A single scanline, 4 two-bit samples packed MSB-first into one byte: 01 10 11 00.
+        let data = ImageData::new(4, 1, png::ColorType::Grayscale, png::BitDepth::Two, vec![0b01_10_11_00]);
+
+        let samples: Vec<usize> = (0..4).map(|x| data.get_pixel_at(x, 0).unwrap().r()).collect();
+        assert_eq!(samples, vec![1, 2, 3, 0]);
+    }
+
+    #[test]
+    fn set_pixel_at_two_bit_depth_preserves_neighbor_samples() {
+        let mut data = ImageData::new(4, 1, png::ColorType::Grayscale, png::BitDepth::Two, vec![0b01_10_11_00]);
+
+        data.set_pixel_at(2, 0, &PixelData::new(png::BitDepth::Two, 1, 0, 0, 0));
+
+        let samples: Vec<usize> = (0..4).map(|x| data.get_pixel_at(x, 0).unwrap().r()).collect();
+        assert_eq!(samples, vec![1, 2, 1, 0]);
+    }
+
+    #[test]
+    fn get_pixel_at_sixteen_bit_depth_reads_big_endian_sample() {
+        let data = ImageData::new(1, 1, png::ColorType::Grayscale, png::BitDepth::Sixteen, vec![0x12, 0x34]);
+
+        assert_eq!(data.get_pixel_at(0, 0).unwrap().r(), 0x1234);
+    }
+
+    #[test]
+    fn set_pixel_at_sixteen_bit_depth_writes_big_endian_sample() {
+        let mut data = ImageData::new(1, 1, png::ColorType::Grayscale, png::BitDepth::Sixteen, vec![0, 0]);
+
+        data.set_pixel_at(0, 0, &PixelData::new(png::BitDepth::Sixteen, 0x1234, 0, 0, 0));
+
+        assert_eq!(data.get_pixel_at(0, 0).unwrap().r(), 0x1234);
+    }
+
+    #[test]
+    fn write_then_read_image_round_trips_indexed_image_with_palette_and_trns() {
+        let palette = vec![0, 0, 0, 10, 20, 30, 40, 50, 60, 70, 80, 90];
+        let trns = vec![255, 128, 0, 255];
+        let pixels = vec![0, 1, 2, 3];
+        let data = ImageData::with_palette(2, 2, png::ColorType::Indexed, png::BitDepth::Eight, pixels, Some(palette), Some(trns));
+
+        let path = std::env::temp_dir().join(format!("master_image_helper_roundtrip_{:?}.png", std::thread::current().id()));
+        let path_str = path.to_str().unwrap();
+
+        image::write_image_to_file(&data, path_str).unwrap();
+        let read_back = image::read_image_from_file(path_str).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(read_back.width(), 2);
+        assert_eq!(read_back.height(), 2);
+        for y in 0..2 {
+            for x in 0..2 {
+                let original = data.get_pixel_at(x, y).unwrap();
+                let roundtripped = read_back.get_pixel_at(x, y).unwrap();
+                assert_eq!((original.r(), original.g(), original.b(), original.a()), (roundtripped.r(), roundtripped.g(), roundtripped.b(), roundtripped.a()));
+            }
+        }
+    }
+
+    fn encode_grayscale_png_bytes(width: u32, height: u32, pixels: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut bytes, width, height);
+            encoder.set_color(png::ColorType::Grayscale);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header().unwrap();
+            writer.write_image_data(pixels).unwrap();
+        }
+        bytes
+    }
+
+    #[test]
+    fn read_image_from_bytes_decodes_an_in_memory_png() {
+        let bytes = encode_grayscale_png_bytes(2, 1, &[10, 20]);
+
+        let data = image::read_image_from_bytes(&bytes, None).unwrap();
+
+        assert_eq!(data.width(), 2);
+        assert_eq!(data.height(), 1);
+        assert_eq!(data.get_pixel_at(0, 0).unwrap().r(), 10);
+        assert_eq!(data.get_pixel_at(1, 0).unwrap().r(), 20);
+    }
+
+    #[test]
+    fn read_image_from_bytes_rejects_buffer_over_max_size() {
+        let bytes = encode_grayscale_png_bytes(2, 1, &[10, 20]);
+
+        let err = image::read_image_from_bytes(&bytes, Some(1)).unwrap_err();
+
+        assert!(matches!(err, image::ImageError::BufferExceedsLimit { size: 2, limit: 1 }));
     }
 }